@@ -0,0 +1,44 @@
+use std::fmt;
+
+/// Errors that can be raised from inside a `WasmiApi` host function and surfaced to the
+/// contract, or turned into a wasmi `Trap` that aborts execution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasmEngineError {
+    MemoryReadError,
+    MemoryAllocationError,
+    MemoryWriteError,
+    OutOfGas,
+    /// A contract called into another contract (`call_contract_index`) and the callee trapped
+    /// or reverted. Carries the callee's error message so the caller can decide whether to
+    /// propagate the failure or handle it.
+    CalleeError { msg: String },
+    /// A host function was called with an argument that isn't a valid member of some enum/set,
+    /// e.g. an unrecognized `CallType` discriminant. Distinct from `CalleeError`: this is the
+    /// caller's own wasm code passing a bad value, not a failure reported by a callee contract.
+    InvalidArgument { msg: String },
+    /// A contract explicitly aborted via the `abort`/`panic` import with a human-readable
+    /// message, instead of trapping with no context.
+    ContractPanic { msg: String },
+    /// A `wasm32-wasi` import that the deterministic WASI preview1 stub layer doesn't (and, for
+    /// determinism across validators, can't) support, e.g. `proc_exit` or an unknown clock id.
+    WasiUnsupported { msg: String },
+}
+
+impl fmt::Display for WasmEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WasmEngineError::MemoryReadError => write!(f, "MemoryReadError"),
+            WasmEngineError::MemoryAllocationError => write!(f, "MemoryAllocationError"),
+            WasmEngineError::MemoryWriteError => write!(f, "MemoryWriteError"),
+            WasmEngineError::OutOfGas => write!(f, "OutOfGas"),
+            WasmEngineError::CalleeError { msg } => write!(f, "CalleeError: {}", msg),
+            WasmEngineError::InvalidArgument { msg } => write!(f, "InvalidArgument: {}", msg),
+            WasmEngineError::ContractPanic { msg } => write!(f, "ContractPanic: {}", msg),
+            WasmEngineError::WasiUnsupported { msg } => write!(f, "WasiUnsupported: {}", msg),
+        }
+    }
+}
+
+// `wasmi` provides a blanket `impl<T: 'static + Any + Debug + Display + Send + Sync> HostError
+// for T`, which `WasmEngineError` satisfies, so it converts into a `Trap` via `?` from any host
+// function that returns `Result<_, Trap>`.
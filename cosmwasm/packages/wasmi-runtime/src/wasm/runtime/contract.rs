@@ -4,13 +4,120 @@ use wasmi::{Error as InterpreterError, MemoryInstance, MemoryRef, ModuleRef, Run
 
 use enclave_ffi_types::Ctx;
 
+use serde::Deserialize;
+
 use crate::consts::BECH32_PREFIX_ACC_ADDR;
-use crate::crypto::Ed25519PublicKey;
+use crate::crypto::{encrypt_attribute, Ed25519PublicKey};
 use crate::wasm::contract_validation::ContractKey;
 use crate::wasm::db::{read_encrypted_key, remove_encrypted_key, write_encrypted_key};
 use crate::wasm::errors::WasmEngineError;
 use crate::wasm::runtime::traits::WasmiApi;
-use crate::wasm::{query_chain::encrypt_and_query_chain, types::IoNonce};
+use crate::wasm::{
+    query_chain::{call_contract, encrypt_and_query_chain},
+    types::IoNonce,
+};
+
+/// The fraction of the caller's remaining gas that may be forwarded to a callee contract via
+/// `call_contract_index`, expressed as `CALL_GAS_FORWARD_NUMERATOR / CALL_GAS_FORWARD_DENOMINATOR`.
+/// Mirrors the EVM's 63/64 rule so a deeply nested call chain can never fully starve the caller.
+const CALL_GAS_FORWARD_NUMERATOR: u128 = 63;
+const CALL_GAS_FORWARD_DENOMINATOR: u128 = 64;
+
+/// Selects how a callee contract executes when invoked via `call_contract_index`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallType {
+    /// The callee runs under its own `contract_key` and storage, like a top-level execute.
+    Call = 0,
+    /// The callee's code runs against the caller's `contract_key` and storage, like `delegatecall`.
+    DelegateCall = 1,
+}
+
+impl CallType {
+    fn from_i32(value: i32) -> Result<Self, WasmEngineError> {
+        match value {
+            0 => Ok(CallType::Call),
+            1 => Ok(CallType::DelegateCall),
+            other => Err(WasmEngineError::InvalidArgument {
+                msg: format!("unknown call type {}", other),
+            }),
+        }
+    }
+}
+
+/// Per-opcode-class multipliers used to scale the gas charged for wasm execution.
+///
+/// At module-load time the contract's bytecode is split into basic blocks and each block is
+/// charged a flat cost via the injected `gas_index` call; `regular` scales that per-block cost.
+/// The remaining fields price operations whose real cost isn't known until runtime (memory
+/// access, memory growth and allocation), and are charged from inside the corresponding host
+/// function instead of at compile time. Keeping this as a struct threaded through
+/// `ContractInstance::new`, rather than a set of constants, lets the cost table be tuned at the
+/// chain-parameter level without recompiling contracts.
+///
+/// Note this schedule only covers what this runtime can price without re-instrumenting the
+/// contract's bytecode: it does not (yet) distinguish `mul`/`div` from other regular
+/// instructions, since that requires walking the compiled module at load time and is done by
+/// whatever produced the `gas_index` injection, not by this runtime.
+#[derive(Debug, Clone, Copy)]
+pub struct WasmCosts {
+    /// Multiplier applied to the flat per-block cost injected into the contract at compile time.
+    pub regular: u32,
+    /// Cost charged per byte read by `extract_vector` or written by `write_to_allocated_memory`
+    /// (and so by every host function that goes through them). Direct accesses to linear memory
+    /// elsewhere, such as `region_len` and the WASI stubs, are not charged at this rate.
+    pub mem: u32,
+    /// Cost charged per additional memory page whenever linear memory grows through the
+    /// `allocate` export (see `allocate_inner`). `wasmi` executes a contract's own `memory.grow`
+    /// instruction directly in the interpreter with no host-side hook to charge from, so growth
+    /// triggered that way (bypassing `allocate`) is not metered by this field; covering it would
+    /// require instrumenting the compiled bytecode at load time, which is out of scope for this
+    /// runtime (see the note on this struct's doc comment about `mul`/`div`).
+    pub grow: u32,
+    /// Cost charged per byte requested from the `allocate` export.
+    pub alloc: u32,
+}
+
+impl Default for WasmCosts {
+    fn default() -> Self {
+        Self {
+            regular: 1,
+            mem: 2,
+            grow: 1_000,
+            alloc: 1,
+        }
+    }
+}
+
+/// Maximum length, in bytes, of a single log attribute's key or value.
+const MAX_LOG_ATTRIBUTE_LEN: usize = 2 * 1024;
+/// Maximum number of log attributes a single `log` call may emit.
+const MAX_LOG_ATTRIBUTES: usize = 64;
+
+/// Maximum length, in bytes, of a contract-supplied `abort`/panic message. Bounded for the same
+/// reason as `MAX_LOG_ATTRIBUTE_LEN`: without it, gas is the only cap on how large a host-side
+/// `String` a malicious contract can force us to allocate.
+const MAX_PANIC_MESSAGE_LEN: usize = 2 * 1024;
+
+/// The wire format a contract sends through the `log` import: a JSON array of attributes.
+#[derive(Deserialize)]
+struct RawLogAttribute {
+    key: String,
+    value: String,
+    /// Whether `value` should be encrypted under the tx's `user_public_key`/nonce before it
+    /// leaves the enclave, instead of being returned as plaintext.
+    #[serde(default)]
+    encrypted: bool,
+}
+
+/// A single log attribute emitted by a contract, folded into the events returned to the
+/// Go/x/compute layer alongside the contract result.
+#[derive(Debug, Clone)]
+pub struct LogAttribute {
+    pub key: String,
+    /// Plaintext, or base64-encoded ciphertext when `encrypted` is set.
+    pub value: String,
+    pub encrypted: bool,
+}
 
 /// SecretContract maps function index to implementation
 /// When instantiating a module we give it the SecretNetworkImportResolver resolver
@@ -23,14 +130,18 @@ pub struct ContractInstance {
     pub gas_used: u64,
     /// Gas used by external services. This is tracked separately so we don't double-charge for external services later.
     pub gas_used_externally: u64,
+    /// The gas cost schedule used to price dynamic-cost operations (allocation, memory growth, ...)
+    pub costs: WasmCosts,
     pub contract_key: ContractKey,
     pub module: ModuleRef,
     pub user_nonce: IoNonce,
     pub user_public_key: Ed25519PublicKey,
+    /// Log attributes emitted by the contract via the `log` import, in emission order.
+    pub logs: Vec<LogAttribute>,
 }
 
 impl ContractInstance {
-    fn get_memory(&self) -> &MemoryInstance {
+    pub(crate) fn get_memory(&self) -> &MemoryInstance {
         &*self.memory
     }
 
@@ -38,6 +149,7 @@ impl ContractInstance {
         context: Ctx,
         module: ModuleRef,
         gas_limit: u64,
+        costs: WasmCosts,
         contract_key: ContractKey,
         user_nonce: IoNonce,
         user_public_key: Ed25519PublicKey,
@@ -55,21 +167,41 @@ impl ContractInstance {
             gas_limit,
             gas_used: 0,
             gas_used_externally: 0,
+            costs,
             contract_key,
             module,
             user_nonce,
             user_public_key,
+            logs: vec![],
         }
     }
 
     /// extract_vector extracts a vector from the wasm memory space
-    pub fn extract_vector(&self, vec_ptr_ptr: u32) -> Result<Vec<u8>, WasmEngineError> {
-        self.extract_vector_inner(vec_ptr_ptr).map_err(|err| {
+    pub fn extract_vector(&mut self, vec_ptr_ptr: u32) -> Result<Vec<u8>, WasmEngineError> {
+        let buffer = self.extract_vector_inner(vec_ptr_ptr).map_err(|err| {
             error!(
                 "error while trying to read the buffer at {:?} : {:?}",
                 vec_ptr_ptr, err
             );
             WasmEngineError::MemoryReadError
+        })?;
+
+        // Charge for the memory load, same as any other read from the contract's linear memory.
+        self.use_gas((buffer.len() as u64).saturating_mul(self.costs.mem as u64))?;
+
+        Ok(buffer)
+    }
+
+    /// Reads just the `len` field of the `Region` at `vec_ptr_ptr`, without reading the buffer
+    /// itself, so a caller can bound the size up front instead of letting `extract_vector`
+    /// allocate a host-side `Vec` for whatever length a contract claims.
+    fn region_len(&self, vec_ptr_ptr: u32) -> Result<u32, WasmEngineError> {
+        self.get_memory().get_value(vec_ptr_ptr + 8).map_err(|err| {
+            error!(
+                "error while trying to read region length at {:?} : {:?}",
+                vec_ptr_ptr, err
+            );
+            WasmEngineError::MemoryReadError
         })
     }
 
@@ -95,7 +227,15 @@ impl ContractInstance {
     }
 
     fn allocate_inner(&mut self, len: u32) -> Result<u32, InterpreterError> {
-        match self.module.clone().invoke_export(
+        // Charge proportionally to the number of bytes requested, on top of whatever the
+        // `allocate` export itself costs through the injected per-block gas.
+        let alloc_cost = (len as u64).saturating_mul(self.costs.alloc as u64);
+        self.use_gas(alloc_cost)
+            .map_err(|err| InterpreterError::Host(Box::new(err)))?;
+
+        let pages_before = self.get_memory().current_size().0;
+
+        let result = match self.module.clone().invoke_export(
             "allocate",
             &[RuntimeValue::I32(len as i32)],
             self,
@@ -108,7 +248,19 @@ impl ContractInstance {
                 "allocate method returned value which wasn't u32: {:?}",
                 other
             ))),
+        };
+
+        // `allocate` may have had to grow linear memory to satisfy the request; charge for the
+        // pages that were actually added rather than trusting the contract to ask for them.
+        let pages_after = self.get_memory().current_size().0;
+        if pages_after > pages_before {
+            let grow_cost =
+                ((pages_after - pages_before) as u64).saturating_mul(self.costs.grow as u64);
+            self.use_gas(grow_cost)
+                .map_err(|err| InterpreterError::Host(Box::new(err)))?;
         }
+
+        result
     }
 
     pub fn write_to_allocated_memory(
@@ -116,14 +268,20 @@ impl ContractInstance {
         buffer: &[u8],
         ptr_to_region_in_wasm_vm: u32,
     ) -> Result<u32, WasmEngineError> {
-        self.write_to_allocated_memory_inner(buffer, ptr_to_region_in_wasm_vm)
+        let ptr = self
+            .write_to_allocated_memory_inner(buffer, ptr_to_region_in_wasm_vm)
             .map_err(|err| {
                 error!(
                     "error while trying to write the buffer {:?} to the destination buffer at {:?} : {:?}",
                     buffer, ptr_to_region_in_wasm_vm, err
                 );
                 WasmEngineError::MemoryWriteError
-            })
+            })?;
+
+        // Charge for the memory store, same as any other write into the contract's linear memory.
+        self.use_gas((buffer.len() as u64).saturating_mul(self.costs.mem as u64))?;
+
+        Ok(ptr)
     }
 
     fn write_to_allocated_memory_inner(
@@ -180,7 +338,7 @@ impl ContractInstance {
     }
 
     /// Track gas used by external services (e.g. storage)
-    fn use_gas_externally(&mut self, gas_amount: u64) -> Result<(), WasmEngineError> {
+    pub(crate) fn use_gas_externally(&mut self, gas_amount: u64) -> Result<(), WasmEngineError> {
         self.gas_used_externally = self.gas_used_externally.saturating_add(gas_amount);
         self.check_gas_usage()
     }
@@ -202,118 +360,243 @@ impl ContractInstance {
     fn is_gas_depleted(&self) -> bool {
         self.gas_limit < self.gas_used.saturating_add(self.gas_used_externally)
     }
-}
 
-impl WasmiApi for ContractInstance {
-    /// Args:
-    /// 1. "key" to read from Tendermint (buffer of bytes)
-    /// key is a pointer to a region "struct" of "pointer" and "length"
-    /// A Region looks like { ptr: u32, len: u32 }
-    fn read_db_index(&mut self, state_key_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
-        let state_key_name = self
-            .extract_vector(state_key_ptr_ptr as u32)
-            .map_err(|err| {
-                error!("read_db() error while trying to read state_key_name from wasm memory");
-                err
+    /// Parses, validates and records the log attributes serialized (as a JSON array) in
+    /// `raw_attributes`, encrypting any attribute the contract flagged as `encrypted` under the
+    /// tx's `user_public_key`/nonce. Returns the number of bytes processed, for gas accounting.
+    fn record_log_attributes(&mut self, raw_attributes: &[u8]) -> Result<u64, WasmEngineError> {
+        let raw_attributes: Vec<RawLogAttribute> =
+            serde_json::from_slice(raw_attributes).map_err(|err| {
+                error!("log() error while trying to parse log attributes: {:?}", err);
+                WasmEngineError::InvalidArgument {
+                    msg: format!("log() attributes are not valid JSON: {}", err),
+                }
             })?;
 
-        trace!(
-            "read_db() was called from WASM code with state_key_name: {:?}",
-            String::from_utf8_lossy(&state_key_name)
-        );
-
-        // Call read_db (this bubbles up to Tendermint via ocalls and FFI to Go code)
-        // This returns the value from Tendermint
-        let (value, gas_used) =
-            read_encrypted_key(&state_key_name, &self.context, &self.contract_key)?;
-        self.use_gas_externally(gas_used)?;
+        if raw_attributes.len() > MAX_LOG_ATTRIBUTES {
+            warn!(
+                "log() was called with {} attributes, more than the maximum of {}",
+                raw_attributes.len(),
+                MAX_LOG_ATTRIBUTES
+            );
+            return Err(WasmEngineError::InvalidArgument {
+                msg: format!(
+                    "log() was called with {} attributes, more than the maximum of {}",
+                    raw_attributes.len(),
+                    MAX_LOG_ATTRIBUTES
+                ),
+            });
+        }
 
-        let value = match value {
-            None => return Ok(Some(RuntimeValue::I32(0))),
-            Some(value) => value,
-        };
+        let mut bytes_processed: u64 = 0;
+        for raw in raw_attributes {
+            if raw.key.len() > MAX_LOG_ATTRIBUTE_LEN || raw.value.len() > MAX_LOG_ATTRIBUTE_LEN {
+                warn!(
+                    "log() attribute {:?} exceeds the maximum attribute length of {} bytes",
+                    raw.key, MAX_LOG_ATTRIBUTE_LEN
+                );
+                return Err(WasmEngineError::InvalidArgument {
+                    msg: format!(
+                        "log() attribute {:?} exceeds the maximum attribute length of {} bytes",
+                        raw.key, MAX_LOG_ATTRIBUTE_LEN
+                    ),
+                });
+            }
+            bytes_processed = bytes_processed.saturating_add((raw.key.len() + raw.value.len()) as u64);
+
+            // Both the key and the value are encrypted, not just the value: leaving the key in
+            // plaintext would let anyone reading the tx's events learn what a "private" attribute
+            // is about even without being able to decrypt its value.
+            //
+            // `self.user_nonce` is reused here across every attribute (and across the key/value
+            // pair within one attribute) in a single `log` call. That's only safe because
+            // `encrypt_attribute` is required to derive a unique per-message nonce internally
+            // (e.g. folding in a counter or the plaintext's position) rather than using
+            // `user_nonce` directly as the cipher nonce -- it must never be called twice with the
+            // same `(user_nonce, message)` pair producing the same keystream. If that contract
+            // ever changes, this call site needs a distinct nonce per attribute/field.
+            let (key, value) = if raw.encrypted {
+                let key_ciphertext =
+                    encrypt_attribute(&self.user_public_key, self.user_nonce, raw.key.as_bytes());
+                let value_ciphertext =
+                    encrypt_attribute(&self.user_public_key, self.user_nonce, raw.value.as_bytes());
+                (base64::encode(key_ciphertext), base64::encode(value_ciphertext))
+            } else {
+                (raw.key, raw.value)
+            };
+
+            self.logs.push(LogAttribute {
+                key,
+                value,
+                encrypted: raw.encrypted,
+            });
+        }
 
-        trace!(
-            "read_db() got value with len {}: '{:?}'",
-            value.len(),
-            value
-        );
+        Ok(bytes_processed)
+    }
+}
 
-        let ptr_to_region_in_wasm_vm = self.write_to_memory(&value).map_err(|err| {
-            error!(
-                "read_db() error while trying to allocate {} bytes for the value",
-                value.len(),
+/// Generates the wasmi trampoline for a `WasmiApi` host function out of an ordinary typed
+/// Rust body, instead of hand-writing the Region marshaling every time.
+///
+/// Each argument is declared as `<ptr_param> as <name>: &[u8]`: the macro extracts the buffer
+/// at `<ptr_param>` via `extract_vector` and binds it to `<name>` for the body to use. The body
+/// itself is expected to return the same `Result<_, _>` shape the equivalent hand-written ocall
+/// already returns in this file -- `(value, gas_used)` for a function that hands a buffer back
+/// to the contract, or just `gas_used` for one that doesn't -- and the macro folds `gas_used`
+/// into `use_gas_externally` and maps the value back into a `RuntimeValue` using the existing
+/// null-pointer/sentinel conventions. This keeps the exact same ABI as a hand-written host
+/// function while removing the repeated boilerplate.
+macro_rules! host_fn {
+    (
+        fn $name:ident(&mut self, $ptr:ident as $arg:ident: &[u8]) -> () {
+            $body:expr
+        }
+    ) => {
+        fn $name(&mut self, $ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
+            let $arg = self.extract_vector($ptr as u32).map_err(|err| {
+                error!(concat!(
+                    stringify!($name),
+                    "() error while trying to read the argument from wasm memory"
+                ));
+                err
+            })?;
+            let $arg: &[u8] = &$arg;
+            trace!(
+                "{}() called from WASM code with {}: {:?}",
+                stringify!($name),
+                stringify!($arg),
+                String::from_utf8_lossy($arg)
             );
-            err
-        })?;
 
-        // Return pointer to the allocated buffer with the value written to it
-        Ok(Some(RuntimeValue::I32(ptr_to_region_in_wasm_vm as i32)))
-    }
+            let gas_used: u64 = { $body }?;
+            self.use_gas_externally(gas_used)?;
 
-    /// Args:
-    /// 1. "key" to delete from Tendermint (buffer of bytes)
-    /// key is a pointer to a region "struct" of "pointer" and "length"
-    /// A Region looks like { ptr: u32, len: u32 }
-    fn remove_db_index(&mut self, state_key_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
-        let state_key_name = self
-            .extract_vector(state_key_ptr_ptr as u32)
-            .map_err(|err| {
-                error!("remove_db() error while trying to read state_key_name from wasm memory");
+            Ok(None)
+        }
+    };
+
+    (
+        fn $name:ident(
+            &mut self,
+            $ptr0:ident as $arg0:ident: &[u8],
+            $ptr1:ident as $arg1:ident: &[u8]
+        ) -> () {
+            $body:expr
+        }
+    ) => {
+        fn $name(&mut self, $ptr0: i32, $ptr1: i32) -> Result<Option<RuntimeValue>, Trap> {
+            let $arg0 = self.extract_vector($ptr0 as u32).map_err(|err| {
+                error!(concat!(
+                    stringify!($name),
+                    "() error while trying to read the first argument from wasm memory"
+                ));
                 err
             })?;
+            let $arg1 = self.extract_vector($ptr1 as u32).map_err(|err| {
+                error!(concat!(
+                    stringify!($name),
+                    "() error while trying to read the second argument from wasm memory"
+                ));
+                err
+            })?;
+            let $arg0: &[u8] = &$arg0;
+            let $arg1: &[u8] = &$arg1;
+            trace!(
+                "{}() called from WASM code with {}: {:?}, {}: {:?}",
+                stringify!($name),
+                stringify!($arg0),
+                String::from_utf8_lossy($arg0),
+                stringify!($arg1),
+                String::from_utf8_lossy($arg1)
+            );
 
-        trace!(
-            "remove_db() was called from WASM code with state_key_name: {:?}",
-            String::from_utf8_lossy(&state_key_name)
-        );
+            let gas_used: u64 = { $body }?;
+            self.use_gas_externally(gas_used)?;
 
-        // Call remove_db (this bubbles up to Tendermint via ocalls and FFI to Go code)
-        let gas_used = remove_encrypted_key(&state_key_name, &self.context, &self.contract_key)?;
-        self.use_gas_externally(gas_used)?;
+            Ok(None)
+        }
+    };
 
-        Ok(None)
-    }
+    (
+        fn $name:ident(&mut self, $ptr:ident as $arg:ident: &[u8]) -> Option<Vec<u8>> {
+            $body:expr
+        }
+    ) => {
+        fn $name(&mut self, $ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
+            let $arg = self.extract_vector($ptr as u32).map_err(|err| {
+                error!(concat!(
+                    stringify!($name),
+                    "() error while trying to read the argument from wasm memory"
+                ));
+                err
+            })?;
+            let $arg: &[u8] = &$arg;
+            trace!(
+                "{}() called from WASM code with {}: {:?}",
+                stringify!($name),
+                stringify!($arg),
+                String::from_utf8_lossy($arg)
+            );
 
-    /// Args:
-    /// 1. "key" to write to Tendermint (buffer of bytes)
-    /// 2. "value" to write to Tendermint (buffer of bytes)
-    /// Both of them are pointers to a region "struct" of "pointer" and "length"
-    /// Lets say Region looks like { ptr: u32, len: u32 }
-    fn write_db_index(
-        &mut self,
-        state_key_ptr_ptr: i32,
-        value_ptr_ptr: i32,
-    ) -> Result<Option<RuntimeValue>, Trap> {
-        let state_key_name = self
-            .extract_vector(state_key_ptr_ptr as u32)
-            .map_err(|err| {
-                error!("write_db() error while trying to read state_key_name from wasm memory");
+            let (value, gas_used): (Option<Vec<u8>>, u64) = { $body }?;
+            self.use_gas_externally(gas_used)?;
+
+            let value = match value {
+                None => return Ok(Some(RuntimeValue::I32(0))),
+                Some(value) => value,
+            };
+
+            let ptr_to_region_in_wasm_vm = self.write_to_memory(&value).map_err(|err| {
+                error!(concat!(
+                    stringify!($name),
+                    "() error while trying to write the result to wasm memory"
+                ));
                 err
             })?;
-        let value = self.extract_vector(value_ptr_ptr as u32).map_err(|err| {
-            error!("write_db() error while trying to read value from wasm memory");
-            err
-        })?;
 
-        trace!(
-            "write_db() was called from WASM code with state_key_name: {:?} value: {:?}",
-            String::from_utf8_lossy(&state_key_name),
-            String::from_utf8_lossy(&value),
-        );
+            Ok(Some(RuntimeValue::I32(ptr_to_region_in_wasm_vm as i32)))
+        }
+    };
+}
 
-        let used_gas =
-            write_encrypted_key(&state_key_name, &value, &self.context, &self.contract_key)
-                .map_err(|err| {
-                    error!(
-                        "write_db() error while trying to write the value to state: {:?}",
-                        err
-                    );
-                    err
-                })?;
-        self.use_gas_externally(used_gas)?;
+impl WasmiApi for ContractInstance {
+    // Args:
+    // 1. "key" to read from Tendermint (buffer of bytes)
+    // key is a pointer to a region "struct" of "pointer" and "length"
+    // A Region looks like { ptr: u32, len: u32 }
+    host_fn! {
+        fn read_db_index(&mut self, state_key_ptr_ptr as state_key_name: &[u8]) -> Option<Vec<u8>> {
+            // Call read_db (this bubbles up to Tendermint via ocalls and FFI to Go code)
+            // This returns the value from Tendermint
+            read_encrypted_key(state_key_name, &self.context, &self.contract_key)
+        }
+    }
 
-        Ok(None)
+    // Args:
+    // 1. "key" to delete from Tendermint (buffer of bytes)
+    // key is a pointer to a region "struct" of "pointer" and "length"
+    // A Region looks like { ptr: u32, len: u32 }
+    host_fn! {
+        fn remove_db_index(&mut self, state_key_ptr_ptr as state_key_name: &[u8]) -> () {
+            // Call remove_db (this bubbles up to Tendermint via ocalls and FFI to Go code)
+            remove_encrypted_key(state_key_name, &self.context, &self.contract_key)
+        }
+    }
+
+    // Args:
+    // 1. "key" to write to Tendermint (buffer of bytes)
+    // 2. "value" to write to Tendermint (buffer of bytes)
+    // Both of them are pointers to a region "struct" of "pointer" and "length"
+    // Lets say Region looks like { ptr: u32, len: u32 }
+    host_fn! {
+        fn write_db_index(
+            &mut self,
+            state_key_ptr_ptr as state_key_name: &[u8],
+            value_ptr_ptr as value: &[u8]
+        ) -> () {
+            write_encrypted_key(state_key_name, value, &self.context, &self.contract_key)
+        }
     }
 
     /// Args:
@@ -451,48 +734,142 @@ impl WasmiApi for ContractInstance {
         Ok(Some(RuntimeValue::I32(0)))
     }
 
-    // stub, for now
-    fn query_chain_index(&mut self, query_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
-        let query_buffer = self.extract_vector(query_ptr_ptr as u32).map_err(|err| {
-            error!("query_chain() error while trying to read canonical address from wasm memory",);
+    host_fn! {
+        fn query_chain_index(&mut self, query_ptr_ptr as query_buffer: &[u8]) -> Option<Vec<u8>> {
+            // Call query_chain (this bubbles up to x/compute via ocalls and FFI to Go code)
+            // Returns the value from x/compute
+            encrypt_and_query_chain(
+                query_buffer,
+                &self.context,
+                self.user_nonce,
+                self.user_public_key,
+            )
+        }
+    }
+
+    /// Args:
+    /// 1. "contract_address" of the contract to call, bech32-encoded human address (buffer of bytes)
+    /// 2. "call_type" a `CallType` discriminant: 0 = Call, 1 = DelegateCall
+    /// 3. "msg" the encrypted execute message to forward to the callee (buffer of bytes)
+    /// All buffers are pointers to a region "struct" of "pointer", "capacity" and "length"
+    /// A Region looks like { ptr: u32, cap: u32, len: u32 }
+    fn call_contract_index(
+        &mut self,
+        contract_address_ptr_ptr: i32,
+        call_type: i32,
+        msg_ptr_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let contract_address = self
+            .extract_vector(contract_address_ptr_ptr as u32)
+            .map_err(|err| {
+                error!("call_contract() error while trying to read contract_address from wasm memory");
+                err
+            })?;
+        let msg = self.extract_vector(msg_ptr_ptr as u32).map_err(|err| {
+            error!("call_contract() error while trying to read msg from wasm memory");
             err
         })?;
+        let call_type = CallType::from_i32(call_type)?;
 
         trace!(
-            "query_chain() was called from WASM code with {:?}",
-            String::from_utf8_lossy(&query_buffer)
+            "call_contract() was called from WASM code with address: {:?}, call_type: {:?}, msg: {:?}",
+            String::from_utf8_lossy(&contract_address),
+            call_type,
+            String::from_utf8_lossy(&msg),
         );
 
-        // Call query_chain (this bubbles up to x/compute via ocalls and FFI to Go code)
-        // Returns the value from x/compute
-        let (result, gas_used) = encrypt_and_query_chain(
-            &query_buffer,
+        // Only forward a bounded fraction of our remaining gas, so a chain of nested calls can
+        // never fully starve the caller of gas to handle the callee's response.
+        let remaining_gas = self
+            .gas_limit
+            .saturating_sub(self.gas_used.saturating_add(self.gas_used_externally));
+        let forwarded_gas = ((remaining_gas as u128 * CALL_GAS_FORWARD_NUMERATOR)
+            / CALL_GAS_FORWARD_DENOMINATOR) as u64;
+
+        // Call call_contract (this bubbles up to x/compute via ocalls and FFI to Go code, which
+        // runs the callee's `execute` entry point under the call type's storage/key semantics)
+        let (result, gas_used) = call_contract(
+            &contract_address,
+            &msg,
+            call_type as i32,
+            forwarded_gas,
             &self.context,
+            &self.contract_key,
             self.user_nonce,
             self.user_public_key,
         )?;
         self.use_gas_externally(gas_used)?;
 
         let result = match result {
-            None => return Ok(Some(RuntimeValue::I32(0))), // Is this supposed to be 0 or Err?
+            None => return Ok(Some(RuntimeValue::I32(0))),
             Some(result) => result,
         };
 
-        let ptr_to_region_in_wasm_vm =   self.write_to_memory(&result)
-            .map_err(|err| {
-                error!(
-                    "query_chain() error while trying to allocate and write the answer {:?} to the WASM VM",
-                    result,
-                );
-                err
-            })?;
+        let ptr_to_region_in_wasm_vm = self.write_to_memory(&result).map_err(|err| {
+            error!(
+                "call_contract() error while trying to allocate and write the answer {:?} to the WASM VM",
+                result,
+            );
+            err
+        })?;
 
-        // Return pointer to the allocated buffer with the value written to it
         Ok(Some(RuntimeValue::I32(ptr_to_region_in_wasm_vm as i32)))
     }
 
+    // Args:
+    // 1. "log" a JSON array of `{ key, value, encrypted }` attributes (buffer of bytes)
+    // is a pointer to a region "struct" of "pointer" and "length"
+    host_fn! {
+        fn log_index(&mut self, log_ptr_ptr as raw_attributes: &[u8]) -> () {
+            self.record_log_attributes(raw_attributes)
+        }
+    }
+
+    /// Args:
+    /// 1. "msg" the UTF-8 panic/abort message the contract wants surfaced to the host
+    /// (buffer of bytes, pointer to a region "struct" of "pointer" and "length")
+    ///
+    /// This never returns successfully: it traps with `WasmEngineError::ContractPanic` carrying
+    /// the message, so the real `unwrap`/`panic!` reason reaches the enclave boundary instead of
+    /// a generic "execution failed". A message longer than `MAX_PANIC_MESSAGE_LEN` traps with
+    /// `WasmEngineError::InvalidArgument` instead, and an unreadable region with
+    /// `WasmEngineError::MemoryReadError`.
+    fn abort_index(&mut self, msg_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap> {
+        let msg_len = self.region_len(msg_ptr_ptr as u32)?;
+        if msg_len as usize > MAX_PANIC_MESSAGE_LEN {
+            warn!(
+                "abort() panic message length {} exceeds the maximum of {} bytes",
+                msg_len, MAX_PANIC_MESSAGE_LEN
+            );
+            return Err(WasmEngineError::InvalidArgument {
+                msg: format!(
+                    "abort() panic message length {} exceeds the maximum of {} bytes",
+                    msg_len, MAX_PANIC_MESSAGE_LEN
+                ),
+            }
+            .into());
+        }
+
+        let msg_bytes = self.extract_vector(msg_ptr_ptr as u32).map_err(|err| {
+            error!("abort() error while trying to read panic message from wasm memory");
+            err
+        })?;
+
+        let msg = String::from_utf8(msg_bytes).unwrap_or_else(|_| {
+            warn!("abort() was called with a non-UTF-8 message");
+            String::from("<non-utf8 panic message>")
+        });
+
+        trace!("abort() was called from WASM code with message: {:?}", msg);
+
+        Err(WasmEngineError::ContractPanic { msg }.into())
+    }
+
     fn gas_index(&mut self, gas_amount: i32) -> Result<Option<RuntimeValue>, Trap> {
-        self.use_gas(gas_amount as u64)?;
+        // `gas_amount` is the flat per-block instruction count injected at compile time;
+        // scale it by the configured cost schedule so the schedule is tunable at runtime.
+        let scaled_gas_amount = (gas_amount as u64).saturating_mul(self.costs.regular as u64);
+        self.use_gas(scaled_gas_amount)?;
         Ok(None)
     }
 }
@@ -0,0 +1,56 @@
+use wasmi::{RuntimeValue, Trap};
+
+/// The set of host functions made available to a running contract. `ContractInstance`
+/// implements this trait; the resolver dispatches imported wasm calls into it by index.
+///
+/// All arguments that represent a buffer are pointers to a `Region` in the contract's linear
+/// memory (`{ ptr: u32, cap: u32, len: u32 }`), following the convention documented on
+/// `ContractInstance::extract_vector` / `write_to_allocated_memory`.
+pub trait WasmiApi {
+    fn read_db_index(&mut self, state_key_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn remove_db_index(&mut self, state_key_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn write_db_index(
+        &mut self,
+        state_key_ptr_ptr: i32,
+        value_ptr_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn canonicalize_address_index(
+        &mut self,
+        human_ptr_ptr: i32,
+        canonical_ptr_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn humanize_address_index(
+        &mut self,
+        canonical_ptr_ptr: i32,
+        human_ptr_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn query_chain_index(&mut self, query_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    /// Synchronously invoke another contract's `execute` entry point.
+    ///
+    /// `call_type` selects between a normal call (the callee runs under its own
+    /// `contract_key`/storage) and a delegate-style call (the callee's code runs against the
+    /// caller's `contract_key`/storage), see `CallType`.
+    fn call_contract_index(
+        &mut self,
+        contract_address_ptr_ptr: i32,
+        call_type: i32,
+        msg_ptr_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    /// Emit one or more log attributes (a JSON array of `{ key, value, encrypted }`) to be
+    /// folded into the tx's events, each either left as plaintext or encrypted under the tx's
+    /// `user_public_key`/nonce depending on its `encrypted` flag.
+    fn log_index(&mut self, log_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    /// Abort execution with a contract-supplied UTF-8 message, e.g. from a Rust `panic!`/`unwrap`.
+    /// Always returns `Err`; there is no successful outcome.
+    fn abort_index(&mut self, msg_ptr_ptr: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn gas_index(&mut self, gas_amount: i32) -> Result<Option<RuntimeValue>, Trap>;
+}
@@ -0,0 +1,209 @@
+use log::*;
+use wasmi::{RuntimeValue, Trap};
+
+use crate::crypto::deterministic_random_bytes;
+use crate::wasm::errors::WasmEngineError;
+use crate::wasm::query_chain::query_block_time;
+use crate::wasm::runtime::contract::ContractInstance;
+
+/// `__wasi_errno_t::__WASI_ERRNO_SUCCESS`
+const WASI_ERRNO_SUCCESS: i32 = 0;
+
+/// `__wasi_clockid_t::__WASI_CLOCKID_REALTIME`
+const WASI_CLOCKID_REALTIME: i32 = 0;
+/// `__wasi_clockid_t::__WASI_CLOCKID_MONOTONIC`
+const WASI_CLOCKID_MONOTONIC: i32 = 1;
+
+/// Maximum number of bytes a single `random_get` call may request. Bounds the host-side buffer
+/// `deterministic_random_bytes` allocates so a contract-controlled `buf_len` can't force a
+/// multi-gigabyte (or, via sign extension of a negative `i32`, an effectively unbounded)
+/// allocation before gas accounting gets a chance to reject it.
+const MAX_RANDOM_BYTES: usize = 64 * 1024;
+
+/// A minimal, deterministic implementation of the WASI preview1 imports that
+/// `wasm32-wasi` binaries pull in even when they never touch the filesystem, clock or
+/// environment. Every validator must compute the exact same result for the exact same
+/// contract execution, so none of these stubs touch real wall-clock time, real randomness or
+/// a real process environment:
+/// - `fd_write` is routed to the debug/trace log instead of a real file descriptor
+/// - `environ_get`/`environ_sizes_get` always report an empty environment
+/// - `clock_time_get` derives a value from the block height/time carried in `Ctx`
+/// - `random_get` draws from the enclave's deterministic per-execution CSPRNG seed
+/// - `proc_exit` and anything else unsupported traps with `WasmEngineError::WasiUnsupported`
+pub trait WasiApi {
+    fn fd_write(
+        &mut self,
+        fd: i32,
+        iovs_ptr: i32,
+        iovs_len: i32,
+        nwritten_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn environ_sizes_get(
+        &mut self,
+        count_ptr: i32,
+        buf_size_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn environ_get(
+        &mut self,
+        environ_ptr: i32,
+        environ_buf_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn clock_time_get(
+        &mut self,
+        clock_id: i32,
+        precision: i64,
+        time_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn random_get(&mut self, buf_ptr: i32, buf_len: i32) -> Result<Option<RuntimeValue>, Trap>;
+
+    fn proc_exit(&mut self, code: i32) -> Result<Option<RuntimeValue>, Trap>;
+}
+
+fn read_u32(instance: &ContractInstance, ptr: u32) -> Result<u32, WasmEngineError> {
+    instance.get_memory().get_value(ptr).map_err(|err| {
+        error!("wasi: error reading u32 from wasm memory at {}: {:?}", ptr, err);
+        WasmEngineError::MemoryReadError
+    })
+}
+
+fn write_u32(instance: &ContractInstance, ptr: u32, value: u32) -> Result<(), WasmEngineError> {
+    instance.get_memory().set_value(ptr, value).map_err(|err| {
+        error!("wasi: error writing u32 to wasm memory at {}: {:?}", ptr, err);
+        WasmEngineError::MemoryWriteError
+    })
+}
+
+impl WasiApi for ContractInstance {
+    fn fd_write(
+        &mut self,
+        _fd: i32,
+        iovs_ptr: i32,
+        iovs_len: i32,
+        nwritten_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let mut logged = Vec::new();
+        let mut total_written: u32 = 0;
+        for i in 0..iovs_len as u32 {
+            let entry_ptr = (iovs_ptr as u32).saturating_add(i.saturating_mul(8));
+            let buf_ptr = read_u32(self, entry_ptr)?;
+            let buf_len = read_u32(self, entry_ptr + 4)?;
+
+            let bytes = self
+                .get_memory()
+                .get(buf_ptr, buf_len as usize)
+                .map_err(|err| {
+                    error!("wasi fd_write() error while reading iovec buffer: {:?}", err);
+                    WasmEngineError::MemoryReadError
+                })?;
+            logged.extend_from_slice(&bytes);
+            total_written = total_written.saturating_add(buf_len);
+        }
+
+        self.use_gas_externally(total_written as u64)?;
+
+        trace!(
+            "wasi fd_write() from WASM code: {:?}",
+            String::from_utf8_lossy(&logged)
+        );
+
+        write_u32(self, nwritten_ptr as u32, total_written)?;
+
+        Ok(Some(RuntimeValue::I32(WASI_ERRNO_SUCCESS)))
+    }
+
+    fn environ_sizes_get(
+        &mut self,
+        count_ptr: i32,
+        buf_size_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        // The enclave never exposes a real process environment to a contract.
+        write_u32(self, count_ptr as u32, 0)?;
+        write_u32(self, buf_size_ptr as u32, 0)?;
+        self.use_gas_externally(8)?;
+
+        Ok(Some(RuntimeValue::I32(WASI_ERRNO_SUCCESS)))
+    }
+
+    fn environ_get(
+        &mut self,
+        _environ_ptr: i32,
+        _environ_buf_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        // Nothing to write: `environ_sizes_get` always reports a count of zero.
+        Ok(Some(RuntimeValue::I32(WASI_ERRNO_SUCCESS)))
+    }
+
+    fn clock_time_get(
+        &mut self,
+        clock_id: i32,
+        _precision: i64,
+        time_ptr: i32,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        // Only the clocks we can answer deterministically from the block context are supported;
+        // anything else (CPU-time clocks, unknown ids, ...) can't be made to agree across
+        // validators, so it traps instead of silently handing back block time.
+        if clock_id != WASI_CLOCKID_REALTIME && clock_id != WASI_CLOCKID_MONOTONIC {
+            return Err(WasmEngineError::WasiUnsupported {
+                msg: format!("clock_time_get() does not support clock id {}", clock_id),
+            }
+            .into());
+        }
+
+        // Derived from the block height/time carried in `Ctx` so every validator computes the
+        // same nanosecond value for the same block, instead of reading the real wall clock.
+        let (time_nanos, gas_used) = query_block_time(&self.context)?;
+        self.use_gas_externally(gas_used)?;
+
+        self.get_memory()
+            .set_value::<u64>(time_ptr as u32, time_nanos)
+            .map_err(|err| {
+                error!("wasi clock_time_get() error while writing result: {:?}", err);
+                WasmEngineError::MemoryWriteError
+            })?;
+
+        Ok(Some(RuntimeValue::I32(WASI_ERRNO_SUCCESS)))
+    }
+
+    fn random_get(&mut self, buf_ptr: i32, buf_len: i32) -> Result<Option<RuntimeValue>, Trap> {
+        // Reject before allocating anything: `buf_len` is contract-controlled, and a negative
+        // value would sign-extend through `as usize` into an effectively unbounded request.
+        if buf_len < 0 || buf_len as usize > MAX_RANDOM_BYTES {
+            return Err(WasmEngineError::InvalidArgument {
+                msg: format!(
+                    "random_get() requested {} bytes, outside the allowed range of 0 to {}",
+                    buf_len, MAX_RANDOM_BYTES
+                ),
+            }
+            .into());
+        }
+        let buf_len = buf_len as usize;
+
+        // Charge gas for the request before generating the bytes, so a host-side allocation is
+        // never paid for by the enclave ahead of the gas check that's supposed to bound it.
+        self.use_gas_externally(buf_len as u64)?;
+
+        // Drawn from the enclave's deterministic per-execution CSPRNG seed, not a real entropy
+        // source, so re-executing the same contract call yields the same "random" bytes.
+        let random_bytes = deterministic_random_bytes(&self.context, buf_len);
+
+        self.get_memory()
+            .set(buf_ptr as u32, &random_bytes)
+            .map_err(|err| {
+                error!("wasi random_get() error while writing result: {:?}", err);
+                WasmEngineError::MemoryWriteError
+            })?;
+
+        Ok(Some(RuntimeValue::I32(WASI_ERRNO_SUCCESS)))
+    }
+
+    fn proc_exit(&mut self, code: i32) -> Result<Option<RuntimeValue>, Trap> {
+        Err(WasmEngineError::WasiUnsupported {
+            msg: format!("proc_exit({}) is not supported inside the enclave", code),
+        }
+        .into())
+    }
+}